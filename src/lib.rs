@@ -6,14 +6,29 @@ use async_openai::{
     self,
     error::OpenAIError,
     types::{
-        ChatCompletionRequestMessageArgs, CreateChatCompletionRequest,
-        CreateChatCompletionRequestArgs, Role,
+        ChatCompletionRequestMessage, ChatCompletionRequestMessageArgs,
+        ChatCompletionRequestMessageContent, ChatCompletionRequestMessageContentPart,
+        ChatCompletionRequestMessageContentPartImage, ChatCompletionRequestMessageContentPartText,
+        CreateChatCompletionRequest, CreateChatCompletionRequestArgs, ImageUrl, Role,
     },
 };
 use async_trait::async_trait;
-use std::{env, error::Error, fmt::Display, time::Duration};
+use backoff::backoff::Backoff;
+use futures::{Stream, StreamExt};
+use std::{
+    env,
+    error::Error,
+    fmt::Display,
+    pin::Pin,
+    time::{Duration, Instant},
+};
 use tiktoken_rs::async_openai::get_chat_completion_max_tokens;
 
+pub mod serve;
+
+/// A stream of incremental token deltas returned by [`Client::ask_question_stream`]
+pub type ChatStream = Pin<Box<dyn Stream<Item = Result<String, ChatError>> + Send>>;
+
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait Client: Send + Sync {
@@ -40,6 +55,165 @@ pub trait Client: Send + Sync {
         model: &str,
         temperature: f32,
     ) -> Result<String, ChatError>;
+
+    /// Asks `OpenAI's` chat API a question and streams the response back as it is generated
+    ///
+    /// Unlike [`Client::ask_question`], this does not honor the server's Retry-After hint on rate
+    /// limits: retrying a partially-streamed response from scratch is a different problem, so this
+    /// keeps the crate's own blind exponential backoff instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `system` - A string representing the system being used
+    /// * `question` - A string representing the question being asked
+    /// * `model` - A string representing the model being used
+    /// * `temperature` - A float representing the temperature of the response
+    ///
+    /// # Returns
+    ///
+    /// A `Stream` yielding incremental chunks of the response as `String`s, or an `Error` if the request failed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request failed.
+    async fn ask_question_stream(
+        &self,
+        system: &str,
+        question: &str,
+        model: &str,
+        temperature: f32,
+    ) -> Result<ChatStream, ChatError>;
+
+    /// Sends a whole conversation, in order, to `OpenAI's` chat API and returns the response as a string
+    ///
+    /// Unlike [`Client::ask_question`], which only ever sends a single system and user message,
+    /// this lets callers maintain a running conversation and pass prior assistant replies back in.
+    ///
+    /// # Arguments
+    ///
+    /// * `messages` - The ordered, role-tagged messages making up the conversation so far
+    /// * `model` - A string representing the model being used
+    /// * `temperature` - A float representing the temperature of the response
+    ///
+    /// # Returns
+    ///
+    /// A `String` representing the response from `OpenAI's` chat API or an `Error` if the request failed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request failed.
+    async fn ask_conversation(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f32,
+    ) -> Result<String, ChatError>;
+
+    /// Sends a whole conversation, in order, to `OpenAI's` chat API and streams the response back
+    /// as it is generated
+    ///
+    /// Unlike [`Client::ask_question_stream`], which only ever sends a single system and user
+    /// message, this lets callers stream a running conversation that includes prior assistant
+    /// replies.
+    ///
+    /// # Arguments
+    ///
+    /// * `messages` - The ordered, role-tagged messages making up the conversation so far
+    /// * `model` - A string representing the model being used
+    /// * `temperature` - A float representing the temperature of the response
+    ///
+    /// # Returns
+    ///
+    /// A `Stream` yielding incremental chunks of the response as `String`s, or an `Error` if the request failed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request failed.
+    async fn ask_conversation_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f32,
+    ) -> Result<ChatStream, ChatError>;
+
+    /// Asks `OpenAI's` chat API a question with one or more attached images
+    ///
+    /// # Arguments
+    ///
+    /// * `system` - A string representing the system being used
+    /// * `question` - A string representing the question being asked
+    /// * `images` - The images to attach alongside the question
+    /// * `model` - A string representing the model being used
+    /// * `temperature` - A float representing the temperature of the response
+    ///
+    /// # Returns
+    ///
+    /// A `String` representing the response from `OpenAI's` chat API or an `Error` if the request failed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request failed, or `ChatErrorKind::UnsupportedCapability` if
+    /// `images` is not empty and `model` does not support vision input.
+    async fn ask_question_with_images(
+        &self,
+        system: &str,
+        question: &str,
+        images: &[ImageInput],
+        model: &str,
+        temperature: f32,
+    ) -> Result<String, ChatError>;
+}
+
+/// A single role-tagged message in a conversation
+#[derive(Debug, Clone)]
+pub struct ChatMessage {
+    pub role: Role,
+    pub content: String,
+}
+
+/// An image attached to a question for a vision-capable model
+#[derive(Debug, Clone)]
+pub enum ImageInput {
+    /// A publicly reachable image URL
+    Url(String),
+    /// Base64-encoded image data together with its MIME type, e.g. `image/png`
+    Base64 { data: String, media_type: String },
+}
+
+impl ImageInput {
+    fn to_url(&self) -> String {
+        match self {
+            Self::Url(url) => url.clone(),
+            Self::Base64 { data, media_type } => format!("data:{media_type};base64,{data}"),
+        }
+    }
+}
+
+/// A capability a model may or may not support
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    Text,
+    Vision,
+}
+
+impl Display for Capability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Vision => write!(f, "vision"),
+        }
+    }
+}
+
+/// Returns the capabilities the given model is known to support
+///
+/// Models not recognized here are assumed to be text-only, which is the conservative default.
+fn capabilities(model: &str) -> &'static [Capability] {
+    if model.starts_with("gpt-4-vision") || model.starts_with("gpt-4o") {
+        &[Capability::Text, Capability::Vision]
+    } else {
+        &[Capability::Text]
+    }
 }
 
 #[derive(Debug)]
@@ -52,6 +226,8 @@ pub struct ChatError {
 pub enum ChatErrorKind {
     Request(OpenAIError),
     BuildRequest(String),
+    /// The selected model does not support the requested capability, e.g. vision
+    UnsupportedCapability(Capability),
 }
 
 impl Display for ChatError {
@@ -59,6 +235,11 @@ impl Display for ChatError {
         match &self.kind {
             ChatErrorKind::BuildRequest(err) => write!(f, "error building chat request {err}"),
             ChatErrorKind::Request(_) => write!(f, "error asking chat model {}", self.model),
+            ChatErrorKind::UnsupportedCapability(capability) => write!(
+                f,
+                "model {} does not support {capability} input",
+                self.model
+            ),
         }
     }
 }
@@ -67,16 +248,28 @@ impl Error for ChatError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match &self.kind {
             ChatErrorKind::Request(err) => Some(err),
-            ChatErrorKind::BuildRequest(_) => None,
+            ChatErrorKind::BuildRequest(_) | ChatErrorKind::UnsupportedCapability(_) => None,
         }
     }
 }
 
 struct TimeoutClient {
+    /// Used for the non-streaming chat calls. Built with a zero-retry backoff (see
+    /// `TimeoutClient::new`) so `async-openai`'s own retry logic never absorbs a 429 internally;
+    /// `create_chat_completion` owns retries itself so it can honor the server's Retry-After hint
+    /// instead of blind exponential backoff.
     client: async_openai::Client,
+    /// Used for `ask_question_stream`. Keeps the crate's own exponential backoff, since retrying a
+    /// partially-streamed response from scratch is a different problem than
+    /// `create_chat_completion` solves; this is an intentional difference, not an oversight.
+    streaming_client: async_openai::Client,
+    timeout: Duration,
 }
 
-/// Creates a new `OpenAI` client with timeout functionality
+/// The API base used when `ClientConfig::api_base` is not set
+const DEFAULT_API_BASE: &str = "https://api.openai.com/v1";
+
+/// Creates a new `OpenAI` client with timeout functionality, talking to the public `api.openai.com` endpoint
 /// # Arguments
 /// * `timeout` - A `Duration` representing the timeout for the client
 /// # Returns
@@ -84,7 +277,36 @@ struct TimeoutClient {
 /// # Errors
 /// Returns an error if the client could not be created.
 pub fn live(timeout: Duration) -> Result<impl Client, CreateClientError> {
-    TimeoutClient::new(timeout)
+    TimeoutClient::new(timeout, ClientConfig::default())
+}
+
+/// Creates a new `OpenAI` client with timeout functionality, using the given `config` to target
+/// Azure `OpenAI`, an `OpenAI`-compatible backend or a proxy
+/// # Arguments
+/// * `timeout` - A `Duration` representing the timeout for the client
+/// * `config` - A `ClientConfig` describing the endpoint, organization and proxy to use
+/// # Returns
+/// A `Client` implementation with timeout functionality
+/// # Errors
+/// Returns an error if the client could not be created.
+pub fn live_with_config(
+    timeout: Duration,
+    config: ClientConfig,
+) -> Result<impl Client, CreateClientError> {
+    TimeoutClient::new(timeout, config)
+}
+
+/// Configuration for targeting a non-default `OpenAI` endpoint
+///
+/// Leaving every field `None` reproduces the behavior of talking directly to `api.openai.com`.
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    /// Overrides the API base, e.g. an Azure `OpenAI` deployment or a local `OpenAI`-compatible server
+    pub api_base: Option<String>,
+    /// Overrides the organization ID sent with every request
+    pub org_id: Option<String>,
+    /// An HTTP or SOCKS5 proxy URL to route requests through
+    pub proxy: Option<String>,
 }
 
 #[derive(Debug)]
@@ -124,6 +346,7 @@ impl TimeoutClient {
     /// # Arguments
     ///
     /// * `timeout` - A `Duration` representing the timeout for the client
+    /// * `config` - A `ClientConfig` describing the endpoint, organization and proxy to use
     ///
     /// # Returns
     ///
@@ -132,32 +355,133 @@ impl TimeoutClient {
     /// # Errors
     ///
     /// Returns an error if the client could not be created or if the `OPENAI_API_KEY` environment variable is not set.
-    fn new(timeout: Duration) -> Result<Self, CreateClientError> {
+    fn new(timeout: Duration, config: ClientConfig) -> Result<Self, CreateClientError> {
         let api_key = env::var("OPENAI_API_KEY").map_err(|err| CreateClientError {
             timeout,
             kind: CreateClientErrorKind::OpenAIKey(err),
         })?;
-        let http_client = reqwest::ClientBuilder::new()
+        let mut http_client_builder = reqwest::ClientBuilder::new()
             // Limiting request duration
-            .timeout(timeout)
+            .timeout(timeout);
+        if let Some(proxy) = config.proxy {
+            let proxy = reqwest::Proxy::all(proxy).map_err(|err| CreateClientError {
+                timeout,
+                kind: CreateClientErrorKind::BuildClient(err),
+            })?;
+            http_client_builder = http_client_builder.proxy(proxy);
+        }
+        let http_client = http_client_builder
             .build()
             .map_err(|err| CreateClientError {
                 timeout,
                 kind: CreateClientErrorKind::BuildClient(err),
             })?;
-        let client = async_openai::Client::new()
-            .with_api_key(api_key)
-            .with_backoff(
-                // It retries on on rate limit error
-                backoff::ExponentialBackoffBuilder::default()
-                    .with_initial_interval(Duration::from_secs(4))
-                    .with_multiplier(2.0)
-                    .with_max_interval(Duration::from_secs(20))
-                    .with_max_elapsed_time(Some(timeout))
-                    .build(),
+        let api_base = config
+            .api_base
+            .unwrap_or_else(|| DEFAULT_API_BASE.to_string());
+
+        let build_client = |backoff: backoff::ExponentialBackoff| {
+            let mut client = async_openai::Client::new()
+                .with_api_key(api_key.clone())
+                .with_http_client(http_client.clone())
+                .with_api_base(api_base.clone())
+                .with_backoff(backoff);
+            if let Some(org_id) = config.org_id.clone() {
+                client = client.with_org_id(org_id);
+            }
+            client
+        };
+
+        let streaming_client = build_client(
+            // It retries on rate limit errors with blind exponential backoff
+            backoff::ExponentialBackoffBuilder::default()
+                .with_initial_interval(Duration::from_secs(4))
+                .with_multiplier(2.0)
+                .with_max_interval(Duration::from_secs(20))
+                .with_max_elapsed_time(Some(timeout))
+                .build(),
+        );
+        // async-openai's `Client::new()` carries its own default retry schedule; without an
+        // explicit zero-retry backoff here, a 429 would be silently retried inside `create()`
+        // before `create_chat_completion`'s Retry-After-aware loop ever saw the error.
+        let client = build_client(
+            backoff::ExponentialBackoffBuilder::default()
+                .with_max_elapsed_time(Some(Duration::ZERO))
+                .build(),
+        );
+
+        Ok(Self {
+            client,
+            streaming_client,
+            timeout,
+        })
+    }
+}
+
+#[cfg(test)]
+mod client_config_tests {
+    use super::{ClientConfig, CreateClientErrorKind, TimeoutClient};
+    use std::time::Duration;
+
+    /// Sets `OPENAI_API_KEY` for the duration of `run`, since `TimeoutClient::new` requires it
+    fn with_api_key<T>(run: impl FnOnce() -> T) -> T {
+        std::env::set_var("OPENAI_API_KEY", "test-key");
+        let result = run();
+        std::env::remove_var("OPENAI_API_KEY");
+        result
+    }
+
+    #[test]
+    fn new_forwards_api_base_and_org_id_to_both_clients() {
+        with_api_key(|| {
+            let client = TimeoutClient::new(
+                Duration::from_secs(30),
+                ClientConfig {
+                    api_base: Some("https://example-proxy.test/v1".to_string()),
+                    org_id: Some("org-123".to_string()),
+                    proxy: None,
+                },
+            )
+            .expect("a valid config should build a client");
+
+            assert_eq!(
+                client.client.config().api_base(),
+                "https://example-proxy.test/v1"
+            );
+            assert_eq!(
+                client.streaming_client.config().api_base(),
+                "https://example-proxy.test/v1"
+            );
+            assert_eq!(
+                client.client.config().headers().get("OpenAI-Organization"),
+                Some(&"org-123".parse().unwrap())
+            );
+            assert_eq!(
+                client
+                    .streaming_client
+                    .config()
+                    .headers()
+                    .get("OpenAI-Organization"),
+                Some(&"org-123".parse().unwrap())
+            );
+        });
+    }
+
+    #[test]
+    fn new_surfaces_an_invalid_proxy_as_a_build_error() {
+        with_api_key(|| {
+            let err = TimeoutClient::new(
+                Duration::from_secs(30),
+                ClientConfig {
+                    api_base: None,
+                    org_id: None,
+                    proxy: Some("not a url".to_string()),
+                },
             )
-            .with_http_client(http_client);
-        Ok(Self { client })
+            .expect_err("an invalid proxy URL should fail to build");
+
+            assert!(matches!(err.kind, CreateClientErrorKind::BuildClient(_)));
+        });
     }
 }
 
@@ -170,17 +494,76 @@ impl Client for TimeoutClient {
         model: &str,
         temperature: f32,
     ) -> Result<String, ChatError> {
-        let request = build_request(system, question, model, temperature)?;
+        let transcript = two_message_transcript(system, question);
+        let request = build_request(&transcript, model, temperature)?;
 
-        let response = self
-            .client
-            .chat()
-            .create(request)
+        let response = self.create_chat_completion(request, model).await?;
+        let mut response_mesasge = String::new();
+
+        for chat_choice in response.choices {
+            response_mesasge.push_str(&chat_choice.message.content);
+        }
+
+        Ok(response_mesasge)
+    }
+
+    async fn ask_question_stream(
+        &self,
+        system: &str,
+        question: &str,
+        model: &str,
+        temperature: f32,
+    ) -> Result<ChatStream, ChatError> {
+        let transcript = two_message_transcript(system, question);
+        self.stream_chat_completion(&transcript, model, temperature)
             .await
-            .map_err(|err| ChatError {
+    }
+
+    async fn ask_conversation(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f32,
+    ) -> Result<String, ChatError> {
+        let request = build_request(messages, model, temperature)?;
+
+        let response = self.create_chat_completion(request, model).await?;
+        let mut response_mesasge = String::new();
+
+        for chat_choice in response.choices {
+            response_mesasge.push_str(&chat_choice.message.content);
+        }
+
+        Ok(response_mesasge)
+    }
+
+    async fn ask_conversation_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f32,
+    ) -> Result<ChatStream, ChatError> {
+        self.stream_chat_completion(messages, model, temperature)
+            .await
+    }
+
+    async fn ask_question_with_images(
+        &self,
+        system: &str,
+        question: &str,
+        images: &[ImageInput],
+        model: &str,
+        temperature: f32,
+    ) -> Result<String, ChatError> {
+        if !images.is_empty() && !capabilities(model).contains(&Capability::Vision) {
+            return Err(ChatError {
                 model: model.to_string(),
-                kind: ChatErrorKind::Request(err),
-            })?;
+                kind: ChatErrorKind::UnsupportedCapability(Capability::Vision),
+            });
+        }
+
+        let request = build_vision_request(system, question, images, model, temperature)?;
+        let response = self.create_chat_completion(request, model).await?;
         let mut response_mesasge = String::new();
 
         for chat_choice in response.choices {
@@ -191,12 +574,386 @@ impl Client for TimeoutClient {
     }
 }
 
+impl TimeoutClient {
+    /// Sends a chat completion request, retrying rate-limited (429) responses
+    ///
+    /// Non-rate-limit errors (bad API keys, unknown models, malformed requests, ...) are returned
+    /// to the caller on the first attempt instead of being retried. When the rate-limit error
+    /// message includes a `"try again in <n>s"` / `"...<n>ms"` hint, that delay is honored exactly
+    /// instead of the default exponential backoff. A wait that would end past the client's
+    /// configured timeout, counted from the first attempt, is not shortened: the call returns the
+    /// rate-limit error instead of sleeping. `self.client` is built with an explicit
+    /// zero-retry backoff (see `TimeoutClient::new`) so that a 429 always surfaces here instead of
+    /// being silently retried inside `async-openai`'s own `create()` first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails for a reason other than rate-limiting, or if the
+    /// server keeps rate-limiting the request past the configured timeout.
+    async fn create_chat_completion(
+        &self,
+        request: CreateChatCompletionRequest,
+        model: &str,
+    ) -> Result<async_openai::types::CreateChatCompletionResponse, ChatError> {
+        let deadline = Instant::now() + self.timeout;
+        let mut backoff = backoff::ExponentialBackoffBuilder::default()
+            .with_initial_interval(Duration::from_secs(4))
+            .with_multiplier(2.0)
+            .with_max_interval(Duration::from_secs(20))
+            .with_max_elapsed_time(Some(self.timeout))
+            .build();
+
+        loop {
+            let err = match self.client.chat().create(request.clone()).await {
+                Ok(response) => return Ok(response),
+                Err(err) => err,
+            };
+
+            if !is_rate_limit_error(&err) {
+                return Err(ChatError {
+                    model: model.to_string(),
+                    kind: ChatErrorKind::Request(err),
+                });
+            }
+
+            let wait = rate_limit_wait(&err)
+                .or_else(|| backoff.next_backoff())
+                .and_then(|wait| within_deadline(wait, Instant::now(), deadline));
+            let Some(wait) = wait else {
+                return Err(ChatError {
+                    model: model.to_string(),
+                    kind: ChatErrorKind::Request(err),
+                });
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Builds and sends a streaming chat completion request for `messages`, shared by
+    /// [`Client::ask_question_stream`] and [`Client::ask_conversation_stream`]
+    async fn stream_chat_completion(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f32,
+    ) -> Result<ChatStream, ChatError> {
+        let mut request = build_request(messages, model, temperature)?;
+        request.stream = Some(true);
+
+        let model = model.to_string();
+        let stream = self
+            .streaming_client
+            .chat()
+            .create_stream(request)
+            .await
+            .map_err(|err| ChatError {
+                model: model.clone(),
+                kind: ChatErrorKind::Request(err),
+            })?;
+
+        let stream = stream.filter_map(move |chunk| {
+            let model = model.clone();
+            async move {
+                match chunk {
+                    Ok(response) => {
+                        let contents = response.choices.into_iter().map(|choice| choice.delta.content);
+                        first_non_empty_delta(contents).map(Ok)
+                    }
+                    Err(err) => Some(Err(ChatError {
+                        model,
+                        kind: ChatErrorKind::Request(err),
+                    })),
+                }
+            }
+        });
+
+        Ok(Box::pin(stream))
+    }
+}
+
+/// Returns `wait` if sleeping for it, starting at `now`, would still land at or before `deadline`
+fn within_deadline(wait: Duration, now: Instant, deadline: Instant) -> Option<Duration> {
+    (now + wait <= deadline).then_some(wait)
+}
+
+/// Picks the first non-empty delta out of a streamed chunk's per-choice contents
+///
+/// A streamed chunk can carry multiple choices, each with its own (possibly absent or empty)
+/// delta; keep-alive chunks in particular carry no content at all and should be dropped rather
+/// than forwarded as an empty string.
+fn first_non_empty_delta(contents: impl IntoIterator<Item = Option<String>>) -> Option<String> {
+    contents
+        .into_iter()
+        .flatten()
+        .find(|content| !content.is_empty())
+}
+
+#[cfg(test)]
+mod stream_chunk_tests {
+    use super::first_non_empty_delta;
+
+    #[test]
+    fn first_non_empty_delta_picks_the_first_choice_with_content() {
+        assert_eq!(
+            first_non_empty_delta([None, Some("hello".to_string()), Some("world".to_string())]),
+            Some("hello".to_string())
+        );
+    }
+
+    #[test]
+    fn first_non_empty_delta_skips_an_empty_string_delta() {
+        assert_eq!(
+            first_non_empty_delta([Some(String::new()), Some("hello".to_string())]),
+            Some("hello".to_string())
+        );
+    }
+
+    #[test]
+    fn first_non_empty_delta_returns_none_when_every_choice_is_empty() {
+        assert_eq!(
+            first_non_empty_delta([None, Some(String::new())]),
+            None
+        );
+    }
+}
+
+/// Returns whether `err` is `OpenAI`'s 429 `rate_limit_exceeded` error, as opposed to e.g. an
+/// invalid API key, an unknown model, or a malformed request - those should surface immediately
+/// rather than being retried
+fn is_rate_limit_error(err: &OpenAIError) -> bool {
+    let OpenAIError::ApiError(api_error) = err else {
+        return false;
+    };
+    api_error.code.as_deref() == Some("rate_limit_exceeded")
+}
+
+/// Reads the server's preferred retry delay off a rate-limit error, parsed out of the `"try again
+/// in <n>s"` / `"...<n>ms"` hint `OpenAI` embeds in the error message
+fn rate_limit_wait(err: &OpenAIError) -> Option<Duration> {
+    let OpenAIError::ApiError(api_error) = err else {
+        return None;
+    };
+    if api_error.code.as_deref() != Some("rate_limit_exceeded") {
+        return None;
+    }
+    parse_retry_after(&api_error.message)
+}
+
+/// Parses a `"Please try again in 20s."` / `"...in 500ms."`-style hint out of an `OpenAI`
+/// rate-limit error message, honoring the unit so a millisecond hint isn't read as seconds
+fn parse_retry_after(message: &str) -> Option<Duration> {
+    let after = message.split("try again in ").nth(1)?;
+    let digits: String = after.chars().take_while(char::is_ascii_digit).collect();
+    if digits.is_empty() {
+        return None;
+    }
+    let value: u64 = digits.parse().ok()?;
+    let unit = &after[digits.len()..];
+    if unit.starts_with("ms") {
+        Some(Duration::from_millis(value))
+    } else if unit.starts_with('s') {
+        Some(Duration::from_secs(value))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod retry_after_tests {
+    use super::{is_rate_limit_error, parse_retry_after, within_deadline};
+    use async_openai::error::{ApiError, OpenAIError};
+    use std::time::{Duration, Instant};
+
+    fn api_error(code: Option<&str>) -> OpenAIError {
+        OpenAIError::ApiError(ApiError {
+            message: "boom".to_string(),
+            r#type: None,
+            param: None,
+            code: code.map(ToString::to_string),
+        })
+    }
+
+    #[test]
+    fn is_rate_limit_error_recognizes_the_rate_limit_code() {
+        assert!(is_rate_limit_error(&api_error(Some("rate_limit_exceeded"))));
+    }
+
+    #[test]
+    fn is_rate_limit_error_rejects_other_api_error_codes() {
+        assert!(!is_rate_limit_error(&api_error(Some(
+            "invalid_api_key"
+        ))));
+        assert!(!is_rate_limit_error(&api_error(None)));
+    }
+
+    #[test]
+    fn parse_retry_after_reads_whole_seconds() {
+        assert_eq!(
+            parse_retry_after("Rate limit reached for requests. Please try again in 20s."),
+            Some(Duration::from_secs(20))
+        );
+    }
+
+    #[test]
+    fn parse_retry_after_reads_milliseconds_rather_than_seconds() {
+        assert_eq!(
+            parse_retry_after("Rate limit reached for requests. Please try again in 500ms."),
+            Some(Duration::from_millis(500))
+        );
+    }
+
+    #[test]
+    fn parse_retry_after_returns_none_without_a_hint() {
+        assert_eq!(parse_retry_after("Rate limit reached for requests."), None);
+    }
+
+    #[test]
+    fn parse_retry_after_returns_none_for_an_unrecognized_unit() {
+        assert_eq!(parse_retry_after("Please try again in 20 minutes."), None);
+    }
+
+    #[test]
+    fn within_deadline_allows_a_wait_that_still_fits() {
+        let now = Instant::now();
+        let deadline = now + Duration::from_secs(10);
+        assert_eq!(
+            within_deadline(Duration::from_secs(5), now, deadline),
+            Some(Duration::from_secs(5))
+        );
+    }
+
+    #[test]
+    fn within_deadline_rejects_a_wait_that_would_overrun() {
+        let now = Instant::now();
+        let deadline = now + Duration::from_secs(10);
+        assert_eq!(
+            within_deadline(Duration::from_secs(20), now, deadline),
+            None
+        );
+    }
+}
+
+#[cfg(test)]
+mod capability_tests {
+    use super::{capabilities, Capability, ChatErrorKind, Client, ImageInput, TimeoutClient};
+    use std::time::Duration;
+
+    fn client() -> TimeoutClient {
+        TimeoutClient {
+            client: async_openai::Client::new(),
+            streaming_client: async_openai::Client::new(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    #[test]
+    fn capabilities_grants_vision_to_gpt4_vision_and_gpt4o_models() {
+        assert!(capabilities("gpt-4-vision-preview").contains(&Capability::Vision));
+        assert!(capabilities("gpt-4o").contains(&Capability::Vision));
+        assert!(capabilities("gpt-4o-mini").contains(&Capability::Vision));
+    }
+
+    #[test]
+    fn capabilities_defaults_unknown_models_to_text_only() {
+        assert_eq!(capabilities("gpt-3.5-turbo"), &[Capability::Text]);
+        assert_eq!(capabilities("gpt-4"), &[Capability::Text]);
+    }
+
+    #[tokio::test]
+    async fn ask_question_with_images_rejects_a_non_vision_model() {
+        let client = client();
+        let images = [ImageInput::Url("https://example.com/cat.png".to_string())];
+
+        let err = client
+            .ask_question_with_images("system", "question", &images, "gpt-3.5-turbo", 1.0)
+            .await
+            .expect_err("a non-vision model with attached images should be rejected");
+
+        assert!(matches!(
+            err.kind,
+            ChatErrorKind::UnsupportedCapability(Capability::Vision)
+        ));
+    }
+}
+
 fn build_request(
+    messages: &[ChatMessage],
+    model: &str,
+    temeperature: f32,
+) -> Result<CreateChatCompletionRequest, ChatError> {
+    let messages = messages
+        .iter()
+        .map(|message| {
+            ChatCompletionRequestMessageArgs::default()
+                .content(message.content.clone())
+                .role(message.role.clone())
+                .build()
+                .map_err(|err| ChatError {
+                    model: model.to_string(),
+                    kind: ChatErrorKind::BuildRequest(err.to_string()),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let max_tokens = max_tokens_for_messages(&messages, model)?;
+
+    let request = CreateChatCompletionRequestArgs::default()
+        .model(model)
+        .max_tokens(max_tokens)
+        .temperature(temeperature)
+        .messages(messages)
+        .build()
+        .map_err(|err| ChatError {
+            model: model.to_string(),
+            kind: ChatErrorKind::BuildRequest(err.to_string()),
+        })?;
+
+    Ok(request)
+}
+
+/// Builds a chat completion request for a question with attached images
+///
+/// The attached images are not accounted for in the `max_tokens` budget, which tiktoken has no
+/// way to size - it is computed from the text portion of the conversation alone.
+fn build_vision_request(
     system: &str,
     question: &str,
+    images: &[ImageInput],
     model: &str,
-    temeperature: f32,
+    temperature: f32,
 ) -> Result<CreateChatCompletionRequest, ChatError> {
+    let transcript = two_message_transcript(system, question);
+    let text_messages = transcript
+        .iter()
+        .map(|message| {
+            ChatCompletionRequestMessageArgs::default()
+                .content(message.content.clone())
+                .role(message.role.clone())
+                .build()
+                .map_err(|err| ChatError {
+                    model: model.to_string(),
+                    kind: ChatErrorKind::BuildRequest(err.to_string()),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let max_tokens = max_tokens_for_messages(&text_messages, model)?;
+
+    let mut content_parts = vec![ChatCompletionRequestMessageContentPart::Text(
+        ChatCompletionRequestMessageContentPartText {
+            text: question.to_string(),
+        },
+    )];
+    content_parts.extend(images.iter().map(|image| {
+        ChatCompletionRequestMessageContentPart::Image(
+            ChatCompletionRequestMessageContentPartImage {
+                image_url: ImageUrl {
+                    url: image.to_url(),
+                    detail: None,
+                },
+            },
+        )
+    }));
+
     let system_message = ChatCompletionRequestMessageArgs::default()
         .content(system)
         .role(Role::System)
@@ -207,7 +964,7 @@ fn build_request(
         })?;
 
     let user_message = ChatCompletionRequestMessageArgs::default()
-        .content(question)
+        .content(ChatCompletionRequestMessageContent::Array(content_parts))
         .role(Role::User)
         .build()
         .map_err(|err| ChatError {
@@ -215,24 +972,11 @@ fn build_request(
             kind: ChatErrorKind::BuildRequest(err.to_string()),
         })?;
 
-    let messages = [system_message, user_message];
-
-    let max_tokens = u16::try_from(get_chat_completion_max_tokens(model, &messages).map_err(
-        |err| ChatError {
-            model: model.to_string(),
-            kind: ChatErrorKind::BuildRequest(err.to_string()),
-        },
-    )?)
-    .map_err(|_| ChatError {
-        model: model.to_string(),
-        kind: ChatErrorKind::BuildRequest("max tokens out of range".to_string()),
-    })?;
-
     let request = CreateChatCompletionRequestArgs::default()
         .model(model)
         .max_tokens(max_tokens)
-        .temperature(temeperature)
-        .messages(messages)
+        .temperature(temperature)
+        .messages([system_message, user_message])
         .build()
         .map_err(|err| ChatError {
             model: model.to_string(),
@@ -241,3 +985,80 @@ fn build_request(
 
     Ok(request)
 }
+
+fn max_tokens_for_messages(
+    messages: &[ChatCompletionRequestMessage],
+    model: &str,
+) -> Result<u16, ChatError> {
+    u16::try_from(
+        get_chat_completion_max_tokens(model, messages).map_err(|err| ChatError {
+            model: model.to_string(),
+            kind: ChatErrorKind::BuildRequest(err.to_string()),
+        })?,
+    )
+    .map_err(|_| ChatError {
+        model: model.to_string(),
+        kind: ChatErrorKind::BuildRequest("max tokens out of range".to_string()),
+    })
+}
+
+#[cfg(test)]
+mod build_request_tests {
+    use super::{build_request, ChatMessage};
+    use async_openai::types::Role;
+
+    fn message(role: Role, content: &str) -> ChatMessage {
+        ChatMessage {
+            role,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn build_request_carries_every_message_of_a_multi_turn_transcript_in_order() {
+        let messages = [
+            message(Role::System, "be helpful"),
+            message(Role::User, "first question"),
+            message(Role::Assistant, "first answer"),
+        ];
+
+        let request = build_request(&messages, "gpt-3.5-turbo", 0.5)
+            .expect("a 3-message transcript should build");
+
+        assert_eq!(request.model, "gpt-3.5-turbo");
+        assert_eq!(request.messages.len(), 3);
+        assert_eq!(request.temperature, Some(0.5));
+    }
+
+    #[test]
+    fn build_request_shrinks_max_tokens_as_the_transcript_grows() {
+        let short = [message(Role::User, "hi")];
+        let long = [
+            message(Role::System, "be helpful"),
+            message(Role::User, "first question"),
+            message(Role::Assistant, "first answer"),
+        ];
+
+        let short_request = build_request(&short, "gpt-3.5-turbo", 0.5)
+            .expect("a 1-message transcript should build");
+        let long_request = build_request(&long, "gpt-3.5-turbo", 0.5)
+            .expect("a 3-message transcript should build");
+
+        assert!(long_request.max_tokens.unwrap() < short_request.max_tokens.unwrap());
+    }
+}
+
+/// Builds the two-message (system, user) transcript used by [`Client::ask_question`] and
+/// [`Client::ask_question_stream`]
+fn two_message_transcript(system: &str, question: &str) -> [ChatMessage; 2] {
+    [
+        ChatMessage {
+            role: Role::System,
+            content: system.to_string(),
+        },
+        ChatMessage {
+            role: Role::User,
+            content: question.to_string(),
+        },
+    ]
+}
@@ -0,0 +1,519 @@
+//! An `OpenAI`-compatible HTTP server that re-serves a [`Client`] behind `/v1/chat/completions`
+//!
+//! This mirrors the shape of aichat's `serve.rs`: any existing `OpenAI` SDK can point its base
+//! URL at this server and transparently pick up the wrapped client's timeout/retry behavior.
+
+use crate::{ChatError, ChatErrorKind, ChatMessage, Client};
+use async_openai::types::Role;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::sse::{Event, Sse},
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::Infallible,
+    error::Error,
+    fmt::Display,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Binds `addr` and serves `client` behind an `OpenAI`-compatible `/v1/chat/completions` endpoint
+///
+/// # Errors
+///
+/// Returns an error if the listener could not be bound or the server fails while running.
+pub async fn serve(client: Arc<dyn Client>, addr: SocketAddr) -> Result<(), ServeError> {
+    let app = Router::new()
+        .route("/v1/chat/completions", post(chat_completions))
+        .with_state(client);
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(ServeError)?;
+
+    axum::serve(listener, app).await.map_err(ServeError)
+}
+
+#[derive(Debug)]
+pub struct ServeError(std::io::Error);
+
+impl Display for ServeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "error serving the openai-compatible endpoint")
+    }
+}
+
+impl Error for ServeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionRequest {
+    model: String,
+    messages: Vec<RequestMessage>,
+    #[serde(default = "default_temperature")]
+    temperature: f32,
+    #[serde(default)]
+    stream: bool,
+}
+
+const fn default_temperature() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RequestMessage {
+    role: String,
+    content: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatCompletionResponse {
+    id: String,
+    object: &'static str,
+    created: u64,
+    model: String,
+    choices: Vec<ResponseChoice>,
+}
+
+#[derive(Debug, Serialize)]
+struct ResponseChoice {
+    index: u32,
+    message: RequestMessage,
+    finish_reason: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatCompletionChunk {
+    id: String,
+    object: &'static str,
+    created: u64,
+    model: String,
+    choices: Vec<ChunkChoice>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChunkChoice {
+    index: u32,
+    delta: ChunkDelta,
+    finish_reason: Option<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChunkDelta {
+    content: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    error: ErrorDetail,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorDetail {
+    message: String,
+}
+
+async fn chat_completions(
+    State(client): State<Arc<dyn Client>>,
+    Json(request): Json<ChatCompletionRequest>,
+) -> Response {
+    let messages = match to_chat_messages(&request.messages) {
+        Ok(messages) => messages,
+        Err(err) => return error_response(StatusCode::BAD_REQUEST, &err),
+    };
+
+    if request.stream {
+        stream_completion(&client, &messages, &request.model, request.temperature).await
+    } else {
+        buffered_completion(&client, &messages, &request.model, request.temperature).await
+    }
+}
+
+/// Counter mixed into generated completion ids so two requests started within the same second
+/// still get distinct ids
+static COMPLETION_ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Generates an `OpenAI`-shaped `chatcmpl-...` id, constant for every chunk of one completion
+fn completion_id() -> String {
+    let sequence = COMPLETION_ID_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("chatcmpl-{}-{sequence}", unix_timestamp())
+}
+
+/// Returns the current unix timestamp in seconds, as `OpenAI`'s `created` field expects
+fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Maps the wire-format messages onto [`ChatMessage`], rejecting roles the client doesn't send
+fn to_chat_messages(messages: &[RequestMessage]) -> Result<Vec<ChatMessage>, String> {
+    messages
+        .iter()
+        .map(|message| {
+            let role = match message.role.as_str() {
+                "system" => Role::System,
+                "user" => Role::User,
+                "assistant" => Role::Assistant,
+                other => return Err(format!("unsupported message role `{other}`")),
+            };
+            Ok(ChatMessage {
+                role,
+                content: message.content.clone(),
+            })
+        })
+        .collect()
+}
+
+async fn buffered_completion(
+    client: &Arc<dyn Client>,
+    messages: &[ChatMessage],
+    model: &str,
+    temperature: f32,
+) -> Response {
+    match client.ask_conversation(messages, model, temperature).await {
+        Ok(content) => Json(ChatCompletionResponse {
+            id: completion_id(),
+            object: "chat.completion",
+            created: unix_timestamp(),
+            model: model.to_string(),
+            choices: vec![ResponseChoice {
+                index: 0,
+                message: RequestMessage {
+                    role: "assistant".to_string(),
+                    content,
+                },
+                finish_reason: "stop",
+            }],
+        })
+        .into_response(),
+        Err(err) => error_response(error_status(&err), &err.to_string()),
+    }
+}
+
+async fn stream_completion(
+    client: &Arc<dyn Client>,
+    messages: &[ChatMessage],
+    model: &str,
+    temperature: f32,
+) -> Response {
+    let stream = match client
+        .ask_conversation_stream(messages, model, temperature)
+        .await
+    {
+        Ok(stream) => stream,
+        Err(err) => return error_response(error_status(&err), &err.to_string()),
+    };
+
+    // Tracks whether the underlying stream ended in an error, so the trailing `[DONE]` marker
+    // (which only a clean completion gets) can be suppressed.
+    let errored = Arc::new(AtomicBool::new(false));
+    let errored_in_scan = Arc::clone(&errored);
+    let model = model.to_string();
+    // One id/created pair, shared by every chunk of this completion, as OpenAI's SDKs expect.
+    let id = completion_id();
+    let created = unix_timestamp();
+
+    let chunks = stream.scan(false, move |done, chunk| {
+        if *done {
+            return futures::future::ready(None);
+        }
+        let event = match chunk {
+            Ok(content) => Event::default().json_data(ChatCompletionChunk {
+                id: id.clone(),
+                object: "chat.completion.chunk",
+                created,
+                model: model.clone(),
+                choices: vec![ChunkChoice {
+                    index: 0,
+                    delta: ChunkDelta { content },
+                    finish_reason: None,
+                }],
+            }),
+            Err(err) => {
+                *done = true;
+                errored_in_scan.store(true, Ordering::Relaxed);
+                Event::default().event("error").json_data(ErrorBody {
+                    error: ErrorDetail {
+                        message: err.to_string(),
+                    },
+                })
+            }
+        };
+        futures::future::ready(Some(Ok::<_, Infallible>(event.unwrap_or_default())))
+    });
+
+    let done_marker = futures::stream::once(async move {
+        (!errored.load(Ordering::Relaxed))
+            .then(|| Ok::<_, Infallible>(Event::default().data("[DONE]")))
+    })
+    .filter_map(futures::future::ready);
+
+    Sse::new(chunks.chain(done_marker)).into_response()
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(ErrorBody {
+            error: ErrorDetail {
+                message: message.to_string(),
+            },
+        }),
+    )
+        .into_response()
+}
+
+/// Maps a [`ChatError`] onto the status code its wire-format error should be served with
+///
+/// `BuildRequest` and `UnsupportedCapability` stem from the caller's own request (an unknown
+/// model, an unsupported capability) and are reported as `400`s; `Request` means the upstream
+/// `OpenAI`-compatible backend itself failed and is reported as a `502`, matching how SDK callers
+/// commonly treat retryability.
+fn error_status(err: &ChatError) -> StatusCode {
+    match err.kind {
+        ChatErrorKind::BuildRequest(_) | ChatErrorKind::UnsupportedCapability(_) => {
+            StatusCode::BAD_REQUEST
+        }
+        ChatErrorKind::Request(_) => StatusCode::BAD_GATEWAY,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        buffered_completion, chat_completions, stream_completion, to_chat_messages,
+        ChatCompletionRequest, RequestMessage,
+    };
+    use crate::{ChatError, ChatErrorKind, ChatMessage, Client, MockClient};
+    use async_openai::types::Role;
+    use axum::body::to_bytes;
+    use axum::extract::{Json, State};
+    use axum::http::StatusCode;
+    use std::sync::Arc;
+
+    #[test]
+    fn to_chat_messages_maps_known_roles_in_order() {
+        let messages = vec![
+            RequestMessage {
+                role: "system".to_string(),
+                content: "be helpful".to_string(),
+            },
+            RequestMessage {
+                role: "user".to_string(),
+                content: "hi".to_string(),
+            },
+            RequestMessage {
+                role: "assistant".to_string(),
+                content: "hello".to_string(),
+            },
+        ];
+
+        let mapped = to_chat_messages(&messages).expect("known roles should map");
+
+        assert!(matches!(mapped[0].role, Role::System));
+        assert!(matches!(mapped[1].role, Role::User));
+        assert!(matches!(mapped[2].role, Role::Assistant));
+        assert_eq!(mapped[1].content, "hi");
+    }
+
+    #[test]
+    fn to_chat_messages_rejects_an_unknown_role() {
+        let messages = vec![RequestMessage {
+            role: "function".to_string(),
+            content: "x".to_string(),
+        }];
+
+        assert!(to_chat_messages(&messages).is_err());
+    }
+
+    #[tokio::test]
+    async fn streaming_requests_forward_the_full_conversation() {
+        let mut client = MockClient::new();
+        client
+            .expect_ask_conversation_stream()
+            .withf(|messages, model, _temperature| messages.len() == 3 && model == "gpt-4o")
+            .returning(|_, _, _| Ok(Box::pin(futures::stream::empty())));
+
+        let request = ChatCompletionRequest {
+            model: "gpt-4o".to_string(),
+            temperature: 1.0,
+            stream: true,
+            messages: vec![
+                RequestMessage {
+                    role: "system".to_string(),
+                    content: "be helpful".to_string(),
+                },
+                RequestMessage {
+                    role: "user".to_string(),
+                    content: "first question".to_string(),
+                },
+                RequestMessage {
+                    role: "assistant".to_string(),
+                    content: "first answer".to_string(),
+                },
+            ],
+        };
+
+        let response =
+            chat_completions(State(Arc::new(client) as Arc<dyn Client>), Json(request)).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn buffered_completion_returns_the_assistant_reply() {
+        let mut client = MockClient::new();
+        client
+            .expect_ask_conversation()
+            .returning(|_, _, _| Ok("hello there".to_string()));
+        let client: Arc<dyn Client> = Arc::new(client);
+
+        let messages = vec![ChatMessage {
+            role: Role::User,
+            content: "hi".to_string(),
+        }];
+        let response = buffered_completion(&client, &messages, "gpt-4o", 1.0).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("hello there"));
+        assert!(text.contains("\"id\":\"chatcmpl-"));
+        assert!(text.contains("\"created\":"));
+    }
+
+    #[tokio::test]
+    async fn buffered_completion_reports_a_build_request_error_as_bad_request() {
+        let mut client = MockClient::new();
+        client.expect_ask_conversation().returning(|_, _, _| {
+            Err(ChatError {
+                model: "unknown-model".to_string(),
+                kind: ChatErrorKind::BuildRequest("unknown model".to_string()),
+            })
+        });
+        let client: Arc<dyn Client> = Arc::new(client);
+
+        let messages = vec![ChatMessage {
+            role: Role::User,
+            content: "hi".to_string(),
+        }];
+        let response = buffered_completion(&client, &messages, "unknown-model", 1.0).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn buffered_completion_reports_an_unsupported_capability_as_bad_request() {
+        let mut client = MockClient::new();
+        client.expect_ask_conversation().returning(|_, _, _| {
+            Err(ChatError {
+                model: "gpt-4o".to_string(),
+                kind: ChatErrorKind::UnsupportedCapability(crate::Capability::Vision),
+            })
+        });
+        let client: Arc<dyn Client> = Arc::new(client);
+
+        let messages = vec![ChatMessage {
+            role: Role::User,
+            content: "hi".to_string(),
+        }];
+        let response = buffered_completion(&client, &messages, "gpt-4o", 1.0).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn buffered_completion_reports_an_upstream_request_error_as_bad_gateway() {
+        let mut client = MockClient::new();
+        client.expect_ask_conversation().returning(|_, _, _| {
+            Err(ChatError {
+                model: "gpt-4o".to_string(),
+                kind: ChatErrorKind::Request(async_openai::error::OpenAIError::ApiError(
+                    async_openai::error::ApiError {
+                        message: "internal server error".to_string(),
+                        r#type: None,
+                        param: None,
+                        code: None,
+                    },
+                )),
+            })
+        });
+        let client: Arc<dyn Client> = Arc::new(client);
+
+        let messages = vec![ChatMessage {
+            role: Role::User,
+            content: "hi".to_string(),
+        }];
+        let response = buffered_completion(&client, &messages, "gpt-4o", 1.0).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn stream_completion_emits_each_chunk_then_a_done_marker() {
+        let mut client = MockClient::new();
+        client
+            .expect_ask_conversation_stream()
+            .returning(|_, _, _| {
+                Ok(Box::pin(futures::stream::iter([
+                    Ok("Hel".to_string()),
+                    Ok("lo".to_string()),
+                ])))
+            });
+        let client: Arc<dyn Client> = Arc::new(client);
+
+        let messages = vec![ChatMessage {
+            role: Role::User,
+            content: "hi".to_string(),
+        }];
+        let response = stream_completion(&client, &messages, "gpt-4o", 1.0).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("Hel"));
+        assert!(text.contains("lo"));
+        assert!(text.contains("[DONE]"));
+        assert!(text.contains("\"id\":\"chatcmpl-"));
+        assert!(text.contains("\"created\":"));
+    }
+
+    #[tokio::test]
+    async fn stream_completion_suppresses_the_done_marker_on_error() {
+        let mut client = MockClient::new();
+        client
+            .expect_ask_conversation_stream()
+            .returning(|_, _, _| {
+                Ok(Box::pin(futures::stream::iter([Err(ChatError {
+                    model: "gpt-4o".to_string(),
+                    kind: ChatErrorKind::BuildRequest("boom".to_string()),
+                })])))
+            });
+        let client: Arc<dyn Client> = Arc::new(client);
+
+        let messages = vec![ChatMessage {
+            role: Role::User,
+            content: "hi".to_string(),
+        }];
+        let response = stream_completion(&client, &messages, "gpt-4o", 1.0).await;
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("event: error"));
+        assert!(!text.contains("[DONE]"));
+    }
+}